@@ -0,0 +1,161 @@
+//! ARM logical-immediate bitmask encoding, i.e. the `DecodeBitMasks` pseudocode
+//! used by the immediate forms of `AND`/`ORR`/`EOR`/`ANDS`, plus its inverse.
+
+use crate::{replicate, Dynamic};
+
+/// Decodes the `N:immr:imms` fields of a logical-immediate instruction into the
+/// 64-bit working mask (`wmask`) and test mask (`tmask`) for an `m`-bit register.
+///
+/// Returns `None` if `imms`/`immr` don't encode a valid logical immediate: `N`
+/// clear with an `imms` of `0b11111x` (no element size of at least two bits),
+/// an element size that doesn't fit `m`, or the reserved
+/// all-ones `imms` pattern within that element size. Panics if `imms`/`immr`
+/// are not 6 bits wide.
+pub fn decode(n: bool, imms: Dynamic, immr: Dynamic, m: u8) -> Option<(u64, u64)> {
+    assert_eq!(imms.bits(), 6);
+    assert_eq!(immr.bits(), 6);
+
+    let imm_n = Dynamic::new(n as u64, 1);
+    let len = imm_n.concat(!imms).highest_set_bit()?;
+    if len == 0 {
+        return None;
+    }
+
+    if m < (1 << len) {
+        return None;
+    }
+
+    let levels = Dynamic::ones(len).zero_extend(6);
+    if (imms & levels) == levels {
+        return None;
+    }
+
+    let s = imms & levels;
+    let r = immr & levels;
+    let d = (s - r) & levels;
+
+    let esize = 1 << len;
+
+    let welem = Dynamic::ones(u8::from(s) + 1).zero_extend(esize);
+    let telem = Dynamic::ones(u8::from(d) + 1).zero_extend(esize);
+
+    let wmask = replicate(welem.rotate_right(r.into()), m / esize);
+    let tmask = replicate(telem, m / esize);
+
+    Some((wmask, tmask))
+}
+
+/// Recovers the `(N, imms, immr)` fields that [`decode`] would turn back into
+/// `value` within an `m`-bit register, or `None` if `value` isn't a legal
+/// logical-immediate (all-zeros, all-ones, and non-periodic/non-contiguous
+/// patterns aren't encodable).
+pub fn encode(value: u64, m: u8) -> Option<(bool, Dynamic, Dynamic)> {
+    let all_ones: u64 = Dynamic::ones(m).into();
+    if value == 0 || value == all_ones {
+        return None;
+    }
+
+    let mut e = 2u8;
+    let esize = loop {
+        if e > 64 {
+            return None;
+        }
+        if m.is_multiple_of(e) {
+            let element = value & ((1u128 << e) - 1) as u64;
+            if replicate(Dynamic::new(element, e), m / e) == value {
+                break e;
+            }
+        }
+        e *= 2;
+    };
+
+    let element: u64 = value & ((1u128 << esize) - 1) as u64;
+    let s_ones = element.count_ones() as u8;
+
+    // The run of set bits is normally contiguous starting at `trailing_zeros`,
+    // but if bit 0 is set the run may wrap around the top of the element; in
+    // that case locate it via the (non-wrapping) run of zero bits instead.
+    let r = if element & 1 == 0 {
+        element.trailing_zeros() as u8
+    } else {
+        let zero_run = !element & ((1u128 << esize) - 1) as u64;
+        let zero_start = zero_run.trailing_zeros() as u8;
+        let zero_len = zero_run.count_ones() as u8;
+        (zero_start + zero_len) % esize
+    };
+
+    let rotation = (esize - r) % esize;
+    let expected = Dynamic::ones(s_ones).zero_extend(esize).rotate_right(rotation);
+    if u64::from(expected) != element {
+        return None;
+    }
+
+    let not_mask = !(esize - 1);
+    let imms_val = (((not_mask as u16) << 1) | (s_ones - 1) as u16) & 0b11_1111;
+
+    let n = esize == 64;
+    let imms = Dynamic::new(imms_val as u64, 6);
+    let immr = Dynamic::new(rotation as u64, 6);
+
+    Some((n, imms, immr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_matches_known_vector() {
+        let (wmask, _) = decode(false, Dynamic::new(0b110000, 6), Dynamic::new(0b000001, 6), 64)
+            .expect("value should be decodable");
+
+        assert_eq!(wmask, 0x8080808080808080);
+    }
+
+    #[test]
+    fn decode_rejects_all_ones_element_size() {
+        assert_eq!(
+            decode(false, Dynamic::new(0b111111, 6), Dynamic::new(0, 6), 64),
+            None
+        );
+    }
+
+    #[test]
+    fn decode_rejects_zero_element_size() {
+        assert_eq!(
+            decode(false, Dynamic::new(0b111110, 6), Dynamic::new(0, 6), 64),
+            None
+        );
+    }
+
+    #[test]
+    fn encode_round_trips_through_decode() {
+        let tests = [
+            (0x8080808080808080, 64),
+            (0x0000ffff0000ffff, 64),
+            (0b1100_1100, 8),
+            // Runs of set bits that wrap around the top of the element.
+            (0x9999999999999999, 64),
+            (0xc3c3c3c3c3c3c3c3, 64),
+        ];
+
+        for (value, m) in tests {
+            let (n, imms, immr) = encode(value, m).expect("value should be encodable");
+            let (wmask, _) = decode(n, imms, immr, m).expect("value should be decodable");
+            assert_eq!(wmask, value);
+        }
+    }
+
+    #[test]
+    fn encode_rejects_all_zeros_and_all_ones() {
+        assert_eq!(encode(0, 64), None);
+        assert_eq!(encode(u64::MAX, 64), None);
+        assert_eq!(encode(0, 32), None);
+        assert_eq!(encode(u32::MAX as u64, 32), None);
+    }
+
+    #[test]
+    fn encode_rejects_non_contiguous_values() {
+        assert_eq!(encode(0b0001_0011, 8), None);
+    }
+}
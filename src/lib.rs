@@ -1,19 +1,59 @@
 use std::{cmp, fmt};
 
+pub mod bitmask;
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Dynamic {
-    val: u8,
+    val: u64,
     bits: u8,
 }
 
-impl From<Dynamic> for i8 {
+impl From<Dynamic> for u64 {
     fn from(value: Dynamic) -> Self {
-        u8::from(value.sign_extend(8)) as i8
+        value.val
+    }
+}
+impl From<Dynamic> for u32 {
+    fn from(value: Dynamic) -> Self {
+        assert!(value.bits <= 32);
+        value.val as u32
+    }
+}
+impl From<Dynamic> for u16 {
+    fn from(value: Dynamic) -> Self {
+        assert!(value.bits <= 16);
+        value.val as u16
     }
 }
 impl From<Dynamic> for u8 {
     fn from(value: Dynamic) -> Self {
-        value.val
+        assert!(value.bits <= 8);
+        value.val as u8
+    }
+}
+
+impl From<Dynamic> for i64 {
+    fn from(value: Dynamic) -> Self {
+        let shift = 64 - value.bits;
+        ((value.val << shift) as i64) >> shift
+    }
+}
+impl From<Dynamic> for i32 {
+    fn from(value: Dynamic) -> Self {
+        assert!(value.bits <= 32);
+        i64::from(value) as i32
+    }
+}
+impl From<Dynamic> for i16 {
+    fn from(value: Dynamic) -> Self {
+        assert!(value.bits <= 16);
+        i64::from(value) as i16
+    }
+}
+impl From<Dynamic> for i8 {
+    fn from(value: Dynamic) -> Self {
+        assert!(value.bits <= 8);
+        i64::from(value) as i8
     }
 }
 
@@ -34,17 +74,19 @@ impl std::ops::BitAnd for Dynamic {
     type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self::Output {
+        assert!(self.bits == rhs.bits);
+
         Dynamic {
             val: self.val & rhs.val,
-            bits: cmp::min(self.bits, rhs.bits),
+            bits: self.bits,
         }
     }
 }
 
-impl std::ops::BitAnd<u8> for Dynamic {
+impl std::ops::BitAnd<u64> for Dynamic {
     type Output = Dynamic;
 
-    fn bitand(self, rhs: u8) -> Self::Output {
+    fn bitand(self, rhs: u64) -> Self::Output {
         Dynamic {
             val: self.val & rhs,
             bits: self.bits,
@@ -56,9 +98,11 @@ impl std::ops::BitOr for Dynamic {
     type Output = Self;
 
     fn bitor(self, rhs: Self) -> Self::Output {
+        assert!(self.bits == rhs.bits);
+
         Dynamic {
             val: self.val | rhs.val,
-            bits: cmp::max(self.bits, rhs.bits),
+            bits: self.bits,
         }
     }
 }
@@ -67,31 +111,110 @@ impl std::ops::Sub for Dynamic {
     type Output = Dynamic;
 
     fn sub(self, rhs: Self) -> Self::Output {
+        self.wrapping_sub(rhs)
+    }
+}
+
+impl std::ops::Add for Dynamic {
+    type Output = Dynamic;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.wrapping_add(rhs)
+    }
+}
+
+impl std::ops::Mul for Dynamic {
+    type Output = Dynamic;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.wrapping_mul(rhs)
+    }
+}
+
+impl std::ops::BitXor for Dynamic {
+    type Output = Dynamic;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
         assert!(self.bits == rhs.bits);
 
-        Dynamic::truncate(self.val.wrapping_sub(rhs.val), self.bits)
+        Dynamic {
+            val: self.val ^ rhs.val,
+            bits: self.bits,
+        }
+    }
+}
+
+impl std::ops::Shl<u8> for Dynamic {
+    type Output = Dynamic;
+
+    fn shl(self, rhs: u8) -> Self::Output {
+        if rhs >= self.bits {
+            Dynamic::new(0, self.bits)
+        } else {
+            Dynamic::truncate(self.val << rhs, self.bits)
+        }
+    }
+}
+
+impl std::ops::Shr<u8> for Dynamic {
+    type Output = Dynamic;
+
+    fn shr(self, rhs: u8) -> Self::Output {
+        if rhs >= self.bits {
+            Dynamic::new(0, self.bits)
+        } else {
+            Dynamic {
+                val: self.val >> rhs,
+                bits: self.bits,
+            }
+        }
+    }
+}
+
+impl std::ops::Neg for Dynamic {
+    type Output = Dynamic;
+
+    fn neg(self) -> Self::Output {
+        Dynamic::new(0, self.bits).wrapping_sub(self)
+    }
+}
+
+impl std::ops::Div for Dynamic {
+    type Output = Dynamic;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).expect("attempt to divide by zero")
+    }
+}
+
+impl std::ops::Rem for Dynamic {
+    type Output = Dynamic;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.checked_rem(rhs)
+            .expect("attempt to calculate the remainder with a divisor of zero")
     }
 }
 
 impl Dynamic {
-    pub fn new(val: u8, bits: u8) -> Self {
-        assert!((1..=8).contains(&bits));
-        assert!(8 - val.leading_zeros() as u8 <= bits);
+    pub fn new(val: u64, bits: u8) -> Self {
+        assert!((1..=64).contains(&bits));
+        assert!(64 - val.leading_zeros() as u8 <= bits);
         Self { val, bits }
     }
-    pub fn truncate(val: u8, bits: u8) -> Self {
+    pub fn truncate(val: u64, bits: u8) -> Self {
         Self::ones(bits) & val
     }
     pub fn ones(count: u8) -> Self {
-        Dynamic::new(((1u16 << count) - 1) as u8, count)
+        Dynamic::new(((1u128 << count) - 1) as u64, count)
     }
 
     pub fn sign_extend(self, new_bits: u8) -> Dynamic {
-        assert!((1..=8).contains(&new_bits));
+        assert!((1..=64).contains(&new_bits));
         assert!(self.bits <= new_bits);
 
-        let sign_bit_mask = 1_u8 << (self.bits - 1);
-        let new_val = (self.val ^ sign_bit_mask) - sign_bit_mask;
+        let sign_bit_mask = 1_u64 << (self.bits - 1);
+        let new_val = (self.val ^ sign_bit_mask).wrapping_sub(sign_bit_mask);
 
         Dynamic::truncate(new_val, new_bits)
     }
@@ -104,6 +227,7 @@ impl Dynamic {
     }
 
     pub fn concat(self, rhs: Dynamic) -> Dynamic {
+        assert!(self.bits + rhs.bits <= 64);
         Dynamic::new((self.val << rhs.bits) | rhs.val, self.bits + rhs.bits)
     }
 
@@ -111,8 +235,25 @@ impl Dynamic {
         self.bits
     }
 
-    pub fn highest_set_bit(self) -> u8 {
-        self.val.ilog2() as u8
+    pub fn highest_set_bit(self) -> Option<u8> {
+        if self.val == 0 {
+            None
+        } else {
+            Some(self.val.ilog2() as u8)
+        }
+    }
+
+    pub fn leading_zeros(self) -> u8 {
+        self.val.leading_zeros() as u8 - (64 - self.bits)
+    }
+    pub fn trailing_zeros(self) -> u8 {
+        cmp::min(self.val.trailing_zeros() as u8, self.bits)
+    }
+    pub fn count_ones(self) -> u8 {
+        self.val.count_ones() as u8
+    }
+    pub fn count_zeros(self) -> u8 {
+        self.bits - self.count_ones()
     }
 
     //          0b101
@@ -122,17 +263,153 @@ impl Dynamic {
     //       0b0_0111
     //  and: 0b0_0110
     pub fn rotate_right(self, len: u8) -> Self {
-        let len = (len % self.bits) as u32;
-        let left_shift = (self.bits as u32 - len) % 8;
-        let new_val = (self.val >> len) | (self.val << left_shift);
+        let len = len % self.bits;
+        if len == 0 {
+            return self;
+        }
+
+        let new_val = (self.val >> len) | (self.val << (self.bits - len));
 
         Dynamic::truncate(new_val, self.bits)
     }
+
+    fn mask(self) -> u128 {
+        (1u128 << self.bits) - 1
+    }
+
+    pub fn wrapping_add(self, rhs: Self) -> Self {
+        self.overflowing_add(rhs).0
+    }
+    pub fn wrapping_sub(self, rhs: Self) -> Self {
+        self.overflowing_sub(rhs).0
+    }
+    pub fn wrapping_mul(self, rhs: Self) -> Self {
+        self.overflowing_mul(rhs).0
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_mul(rhs) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        assert!(self.bits == rhs.bits);
+
+        if rhs.val == 0 {
+            return None;
+        }
+        Some(Dynamic::new(self.val / rhs.val, self.bits))
+    }
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        assert!(self.bits == rhs.bits);
+
+        if rhs.val == 0 {
+            return None;
+        }
+        Some(Dynamic::new(self.val % rhs.val, self.bits))
+    }
+
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        assert!(self.bits == rhs.bits);
+
+        let full = self.val as u128 + rhs.val as u128;
+        let result = Dynamic::new((full & self.mask()) as u64, self.bits);
+
+        (result, full > self.mask())
+    }
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        assert!(self.bits == rhs.bits);
+
+        let result = Dynamic::truncate(self.val.wrapping_sub(rhs.val), self.bits);
+
+        (result, self.val < rhs.val)
+    }
+    pub fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        assert!(self.bits == rhs.bits);
+
+        let full = self.val as u128 * rhs.val as u128;
+        let result = Dynamic::new((full & self.mask()) as u64, self.bits);
+
+        (result, full > self.mask())
+    }
+}
+
+impl Dynamic {
+    fn byte_len(self) -> usize {
+        (self.bits as usize).div_ceil(8)
+    }
+
+    pub fn to_be_bytes(self) -> Vec<u8> {
+        self.val.to_be_bytes()[8 - self.byte_len()..].to_vec()
+    }
+    pub fn to_le_bytes(self) -> Vec<u8> {
+        self.val.to_le_bytes()[..self.byte_len()].to_vec()
+    }
+
+    pub fn from_be_bytes(bytes: &[u8], bits: u8) -> Self {
+        let len = (bits as usize).div_ceil(8);
+        assert!(bytes.len() == len);
+
+        let mut buf = [0u8; 8];
+        buf[8 - len..].copy_from_slice(bytes);
+        Dynamic::new(u64::from_be_bytes(buf), bits)
+    }
+    pub fn from_le_bytes(bytes: &[u8], bits: u8) -> Self {
+        let len = (bits as usize).div_ceil(8);
+        assert!(bytes.len() == len);
+
+        let mut buf = [0u8; 8];
+        buf[..len].copy_from_slice(bytes);
+        Dynamic::new(u64::from_le_bytes(buf), bits)
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("{self:x}")
+    }
+    pub fn from_hex(s: &str, bits: u8) -> Result<Self, std::num::ParseIntError> {
+        let val = u64::from_str_radix(s, 16)?;
+        Ok(Dynamic::new(val, bits))
+    }
+}
+
+impl fmt::LowerHex for Dynamic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{0:0width$x}",
+            self.val,
+            width = (self.bits as usize).div_ceil(4)
+        )
+    }
+}
+impl fmt::UpperHex for Dynamic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{0:0width$X}",
+            self.val,
+            width = (self.bits as usize).div_ceil(4)
+        )
+    }
 }
 
 pub fn replicate(val: Dynamic, count: u8) -> u64 {
     let shift = val.bits();
-    let mut val: u64 = u8::from(val).into();
+    let mut val: u64 = val.into();
 
     for _ in 1..count {
         val |= val << shift
@@ -160,7 +437,7 @@ mod tests {
 
         for (highest_bit, val) in tests.iter().enumerate() {
             let val = Dynamic::new(*val, 8);
-            assert_eq!(highest_bit as u8, val.highest_set_bit());
+            assert_eq!(Some(highest_bit as u8), val.highest_set_bit());
         }
     }
     #[test]
@@ -207,7 +484,7 @@ mod tests {
     fn succeeding_new() {
         for bits in 1u8..=8 {
             for x in 0..2u16.pow(bits.into()) {
-                Dynamic::new(x.try_into().unwrap(), bits);
+                Dynamic::new(x.into(), bits);
             }
         }
     }
@@ -223,7 +500,7 @@ mod tests {
             (5, 0b00100000),
             (6, 0b01000000),
             (7, 0b10000000),
-            (9, 0),
+            (65, 0),
         ];
 
         for (bits, val) in tests {
@@ -238,7 +515,7 @@ mod tests {
         for bits in 1..=8 {
             let all_bits_set: u8 = Dynamic::ones(bits).into();
             for val in 0..=all_bits_set {
-                let truncated: u8 = Dynamic::truncate(val, bits).into();
+                let truncated: u8 = Dynamic::truncate(val.into(), bits).into();
                 assert_eq!(truncated, val);
             }
         }
@@ -249,9 +526,176 @@ mod tests {
         for bits in 1..8 {
             let all_bits_set: u8 = Dynamic::ones(bits).into();
             for val in all_bits_set..u8::MAX {
-                let truncated: u8 = Dynamic::truncate(val, bits).into();
+                let truncated: u8 = Dynamic::truncate(val.into(), bits).into();
                 assert_eq!(truncated >> bits, 0);
             }
         }
     }
+
+    #[test]
+    fn ones_up_to_64_bits() {
+        assert_eq!(u64::from(Dynamic::ones(64)), u64::MAX);
+        assert_eq!(u64::from(Dynamic::ones(63)), u64::MAX >> 1);
+        assert_eq!(u64::from(Dynamic::ones(32)), u32::MAX as u64);
+    }
+
+    #[test]
+    fn concat_up_to_64_bits() {
+        let lhs = Dynamic::new(u32::MAX as u64, 32);
+        let rhs = Dynamic::new(u32::MAX as u64, 32);
+
+        let concatenated = lhs.concat(rhs);
+        assert_eq!(concatenated.bits(), 64);
+        assert_eq!(u64::from(concatenated), u64::MAX);
+    }
+
+    #[test]
+    fn sign_extend_to_64_bits() {
+        let negative_one = Dynamic::new(0b1, 1);
+        assert_eq!(i64::from(negative_one.sign_extend(64)), -1);
+
+        let min_i32 = Dynamic::new(1 << 31, 32);
+        assert_eq!(i64::from(min_i32.sign_extend(64)), i32::MIN as i64);
+    }
+
+    #[test]
+    fn wide_conversions() {
+        let val = Dynamic::new(u64::MAX, 64);
+        assert_eq!(u64::from(val), u64::MAX);
+        assert_eq!(i64::from(val), -1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn narrowing_conversions_reject_excess_width() {
+        let val = Dynamic::new(0x1234, 16);
+        let _ = u8::from(val);
+    }
+
+    #[test]
+    fn add_wraps() {
+        let a = Dynamic::new(0b1111, 4);
+        let b = Dynamic::new(0b0001, 4);
+        assert_eq!(u8::from(a + b), 0);
+    }
+
+    #[test]
+    fn mul_wraps() {
+        let a = Dynamic::new(0b1111, 4);
+        let b = Dynamic::new(0b0010, 4);
+        assert_eq!(u8::from(a * b), 0b1110);
+    }
+
+    #[test]
+    fn overflowing_add_reports_overflow() {
+        let a = Dynamic::new(0b1111, 4);
+        let b = Dynamic::new(0b0001, 4);
+        let (result, overflow) = a.overflowing_add(b);
+        assert_eq!(u8::from(result), 0);
+        assert!(overflow);
+
+        let (result, overflow) = a.overflowing_add(Dynamic::new(0, 4));
+        assert_eq!(u8::from(result), 0b1111);
+        assert!(!overflow);
+    }
+
+    #[test]
+    fn checked_sub_detects_underflow() {
+        let a = Dynamic::new(0b0001, 4);
+        let b = Dynamic::new(0b0010, 4);
+        assert_eq!(a.checked_sub(b), None);
+        assert_eq!(b.checked_sub(a).map(u8::from), Some(0b0001));
+    }
+
+    #[test]
+    fn checked_mul_detects_overflow() {
+        let a = Dynamic::new(0b1111, 4);
+        let b = Dynamic::new(0b0010, 4);
+        assert_eq!(a.checked_mul(b), None);
+        assert_eq!(
+            a.checked_mul(Dynamic::new(0b0001, 4)).map(u8::from),
+            Some(0b1111)
+        );
+    }
+
+    #[test]
+    fn shift_operators_respect_width() {
+        let val = Dynamic::new(0b0011, 4);
+        assert_eq!(u8::from(val << 1), 0b0110);
+        assert_eq!(u8::from(val << 4), 0);
+        assert_eq!(u8::from(val >> 1), 0b0001);
+        assert_eq!(u8::from(val >> 4), 0);
+    }
+
+    #[test]
+    fn neg_is_twos_complement() {
+        let val = Dynamic::new(0b0001, 4);
+        assert_eq!(i8::from(-val), -1);
+        assert_eq!(u8::from(-Dynamic::new(0, 4)), 0);
+    }
+
+    #[test]
+    fn bitxor_of_matching_widths() {
+        let a = Dynamic::new(0b1100, 4);
+        let b = Dynamic::new(0b1010, 4);
+        assert_eq!(u8::from(a ^ b), 0b0110);
+    }
+
+    #[test]
+    fn byte_round_trip() {
+        let val = Dynamic::new(0x1234, 16);
+        assert_eq!(val.to_be_bytes(), [0x12, 0x34]);
+        assert_eq!(val.to_le_bytes(), [0x34, 0x12]);
+
+        assert_eq!(Dynamic::from_be_bytes(&[0x12, 0x34], 16), val);
+        assert_eq!(Dynamic::from_le_bytes(&[0x34, 0x12], 16), val);
+
+        let odd_width = Dynamic::new(0x1, 12);
+        assert_eq!(odd_width.to_be_bytes(), [0x00, 0x1]);
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let val = Dynamic::new(0x2a, 8);
+        assert_eq!(val.to_hex(), "2a");
+        assert_eq!(Dynamic::from_hex("2a", 8).unwrap(), val);
+
+        assert_eq!(format!("{val:x}"), "2a");
+        assert_eq!(format!("{val:X}"), "2A");
+
+        let padded = Dynamic::new(0b1010, 12);
+        assert_eq!(format!("{padded:x}"), "00a");
+
+        Dynamic::from_hex("zz", 8).unwrap_err();
+    }
+
+    #[test]
+    fn highest_set_bit_of_zero_is_none() {
+        assert_eq!(Dynamic::new(0, 8).highest_set_bit(), None);
+    }
+
+    #[test]
+    fn div_and_rem() {
+        let a = Dynamic::new(10, 8);
+        let b = Dynamic::new(3, 8);
+        assert_eq!(u8::from(a / b), 3);
+        assert_eq!(u8::from(a % b), 1);
+
+        let zero = Dynamic::new(0, 8);
+        assert_eq!(a.checked_div(zero), None);
+        assert_eq!(a.checked_rem(zero), None);
+    }
+
+    #[test]
+    fn bit_counting_is_relative_to_bits() {
+        let val = Dynamic::new(0b0010, 4);
+        assert_eq!(val.leading_zeros(), 2);
+        assert_eq!(val.trailing_zeros(), 1);
+        assert_eq!(val.count_ones(), 1);
+        assert_eq!(val.count_zeros(), 3);
+
+        let zero = Dynamic::new(0, 4);
+        assert_eq!(zero.leading_zeros(), 4);
+        assert_eq!(zero.trailing_zeros(), 4);
+    }
 }